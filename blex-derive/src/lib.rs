@@ -0,0 +1,131 @@
+//! `#[derive(FromTokens)]`: declares a token grammar as attributes on an
+//! enum instead of hand-writing rule closures.
+//!
+//! ```
+//! use blex::{process_rule, str_to_tokens, FromTokens, Token};
+//!
+//! #[derive(blex_derive::FromTokens)]
+//! enum Grammar {
+//!     #[blex(seq = "digit+", tag = "int")]
+//!     Int,
+//!     #[blex(one = "ws")]
+//!     Whitespace,
+//! }
+//!
+//! fn tag_chars(mut tokens: Vec<Token>) -> Option<Vec<Token>> {
+//!     for tok in &mut tokens {
+//!         let ch = tok.single_char().unwrap_or_default();
+//!         if ch.is_ascii_digit() {
+//!             tok.tags.push("digit");
+//!         } else if ch.is_whitespace() {
+//!             tok.tags.push("ws");
+//!         }
+//!     }
+//!     Some(tokens)
+//! }
+//!
+//! let mut body = str_to_tokens("12 3");
+//! process_rule(tag_chars, &mut body);
+//! process_rule(Grammar::rule(), &mut body);
+//! assert!(body[0].has_tag("int"));
+//! assert_eq!(body[0].content(), "12");
+//! ```
+//!
+//! Each variant must be a unit variant carrying a `#[blex(...)]` attribute:
+//! `seq`/`one` give the pattern (the same literal/class/concat/alt/`+`/`*`/
+//! `?` grammar as `blex::automaton`'s pattern AST: a quoted `'x'` for a
+//! literal character, `[a-z]` for a character class, or a bare word for a
+//! tag reference), and `tag` gives the tag(s) applied to the matched span
+//! (defaulting to the variant's name if omitted). Earlier variants take
+//! priority where patterns overlap.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+mod pattern;
+
+use pattern::Pattern;
+
+#[proc_macro_derive(FromTokens, attributes(blex))]
+pub fn derive_from_tokens(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let Data::Enum(data) = &input.data else {
+        return syn::Error::new_spanned(&input, "FromTokens can only be derived for enums")
+            .to_compile_error()
+            .into();
+    };
+
+    let mut matcher_exprs = vec![];
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return syn::Error::new_spanned(variant, "FromTokens variants must be unit variants")
+                .to_compile_error()
+                .into();
+        }
+
+        let Some(attr) = variant.attrs.iter().find(|attr| attr.path().is_ident("blex")) else {
+            return syn::Error::new_spanned(
+                variant,
+                "every FromTokens variant needs a #[blex(...)] attribute",
+            )
+            .to_compile_error()
+            .into();
+        };
+
+        let mut pattern_source: Option<String> = None;
+        let mut tags: Vec<String> = vec![];
+        let parse_result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("seq") || meta.path.is_ident("one") {
+                let value: LitStr = meta.value()?.parse()?;
+                pattern_source = Some(value.value());
+            } else if meta.path.is_ident("tag") {
+                let value: LitStr = meta.value()?.parse()?;
+                tags.push(value.value());
+            }
+            Ok(())
+        });
+        if let Err(err) = parse_result {
+            return err.to_compile_error().into();
+        }
+
+        let Some(pattern_source) = pattern_source else {
+            return syn::Error::new_spanned(attr, "expected `seq = \"...\"` or `one = \"...\"`")
+                .to_compile_error()
+                .into();
+        };
+        let pattern = match Pattern::parse(&pattern_source) {
+            Ok(pattern) => pattern,
+            Err(message) => return syn::Error::new_spanned(attr, message).to_compile_error().into(),
+        };
+        if tags.is_empty() {
+            tags.push(variant.ident.to_string());
+        }
+
+        matcher_exprs.push(pattern.to_matcher_expr(&tags));
+    }
+
+    let Some(combined) = matcher_exprs
+        .into_iter()
+        .rev()
+        .reduce(|rest, first| quote! { ::blex::matcher::alt(#first, #rest) })
+    else {
+        return syn::Error::new_spanned(&input, "FromTokens requires at least one variant")
+            .to_compile_error()
+            .into();
+    };
+
+    let expanded = quote! {
+        impl ::blex::FromTokens for #name {
+            fn rule() -> impl Fn(Vec<::blex::Token>) -> Option<Vec<::blex::Token>> {
+                ::blex::matcher::into_rule(#combined)
+            }
+        }
+    };
+
+    expanded.into()
+}
@@ -0,0 +1,272 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// A parsed `#[blex(seq = "...")]` pattern: the same literal/class/concat/
+/// alt/`+`/`*`/`?` grammar the `blex::automaton` pattern AST uses. An atom
+/// is one of a quoted literal character (`'a'`), a bracketed character
+/// class (`[a-z0-9_]`), or a bare tag name (`digit`) referencing a tag
+/// already applied by an earlier rule. Atoms can carry a trailing `+`/`*`/
+/// `?` quantifier, are concatenated by whitespace, and alternated with `|`.
+pub enum Pattern {
+    Char(char),
+    Class(Vec<(char, char)>),
+    Tag(String),
+    Concat(Vec<Pattern>),
+    Alt(Vec<Pattern>),
+    Star(Box<Pattern>),
+    Plus(Box<Pattern>),
+    Opt(Box<Pattern>),
+}
+
+impl Pattern {
+    /// Parses a pattern source string such as `"digit+"`, `"'-'? digit+"`,
+    /// `"[a-zA-Z_] [a-zA-Z0-9_]*"`, or `"int|float"` for alternation.
+    pub fn parse(source: &str) -> Result<Pattern, String> {
+        let mut parser = Parser {
+            chars: source.chars().peekable(),
+        };
+        let pattern = parser.parse_alt(source)?;
+        parser.skip_whitespace();
+        if parser.chars.peek().is_some() {
+            return Err(format!("unexpected trailing input in `{}`", source));
+        }
+        Ok(pattern)
+    }
+
+    /// Renders this pattern as a `blex::matcher` combinator expression,
+    /// folding whatever it consumes into a single token tagged with `tags`.
+    pub fn to_matcher_expr(&self, tags: &[String]) -> TokenStream {
+        let inner = self.to_inner_expr();
+        quote! { ::blex::matcher::Matcher::wrap_as(#inner, vec![#(#tags),*]) }
+    }
+
+    fn to_inner_expr(&self) -> TokenStream {
+        match self {
+            Pattern::Char(ch) => {
+                let literal = ch.to_string();
+                quote! { ::blex::matcher::tag(#literal) }
+            }
+            Pattern::Class(ranges) => {
+                let ranges = ranges.iter().map(|(lo, hi)| quote! { (#lo, #hi) });
+                quote! { ::blex::matcher::char_class(vec![#(#ranges),*]) }
+            }
+            Pattern::Tag(name) => quote! { ::blex::matcher::tag(#name) },
+            Pattern::Concat(parts) => parts
+                .iter()
+                .map(Pattern::to_inner_expr)
+                .reduce(|a, b| quote! { ::blex::matcher::seq(#a, #b) })
+                .expect("Concat requires at least one pattern"),
+            Pattern::Alt(parts) => parts
+                .iter()
+                .map(Pattern::to_inner_expr)
+                .reduce(|a, b| quote! { ::blex::matcher::alt(#a, #b) })
+                .expect("Alt requires at least one pattern"),
+            Pattern::Star(inner) => {
+                let inner = inner.to_inner_expr();
+                quote! { ::blex::matcher::Matcher::optional(::blex::matcher::Matcher::many1(#inner)) }
+            }
+            Pattern::Plus(inner) => {
+                let inner = inner.to_inner_expr();
+                quote! { ::blex::matcher::Matcher::many1(#inner) }
+            }
+            Pattern::Opt(inner) => {
+                let inner = inner.to_inner_expr();
+                quote! { ::blex::matcher::Matcher::optional(#inner) }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_alt(&mut self, source: &str) -> Result<Pattern, String> {
+        let mut branches = vec![self.parse_concat(source)?];
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() == Some(&'|') {
+                self.chars.next();
+                branches.push(self.parse_concat(source)?);
+            } else {
+                break;
+            }
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().unwrap()
+        } else {
+            Pattern::Alt(branches)
+        })
+    }
+
+    fn parse_concat(&mut self, source: &str) -> Result<Pattern, String> {
+        let mut terms = vec![];
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                None | Some('|') => break,
+                _ => terms.push(self.parse_quantified(source)?),
+            }
+        }
+        if terms.is_empty() {
+            return Err(format!("empty pattern branch in `{}`", source));
+        }
+        Ok(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Pattern::Concat(terms)
+        })
+    }
+
+    fn parse_quantified(&mut self, source: &str) -> Result<Pattern, String> {
+        let atom = self.parse_atom(source)?;
+        Ok(match self.chars.peek() {
+            Some('+') => {
+                self.chars.next();
+                Pattern::Plus(Box::new(atom))
+            }
+            Some('*') => {
+                self.chars.next();
+                Pattern::Star(Box::new(atom))
+            }
+            Some('?') => {
+                self.chars.next();
+                Pattern::Opt(Box::new(atom))
+            }
+            _ => atom,
+        })
+    }
+
+    fn parse_atom(&mut self, source: &str) -> Result<Pattern, String> {
+        match self.chars.peek() {
+            Some('\'') => self.parse_char_literal(source),
+            Some('[') => self.parse_class(source),
+            Some(c) if c.is_alphanumeric() || *c == '_' => Ok(self.parse_tag()),
+            Some(c) => Err(format!("unexpected character `{}` in `{}`", c, source)),
+            None => Err(format!("expected an atom in `{}`", source)),
+        }
+    }
+
+    fn parse_char_literal(&mut self, source: &str) -> Result<Pattern, String> {
+        self.chars.next(); // opening quote
+        let ch = self
+            .chars
+            .next()
+            .ok_or_else(|| format!("unterminated character literal in `{}`", source))?;
+        match self.chars.next() {
+            Some('\'') => Ok(Pattern::Char(ch)),
+            _ => Err(format!("unterminated character literal in `{}`", source)),
+        }
+    }
+
+    fn parse_class(&mut self, source: &str) -> Result<Pattern, String> {
+        self.chars.next(); // opening '['
+        let mut ranges = vec![];
+        loop {
+            match self.chars.next() {
+                Some(']') => break,
+                Some(lo) => {
+                    if self.chars.peek() == Some(&'-') {
+                        self.chars.next();
+                        let hi = self
+                            .chars
+                            .next()
+                            .ok_or_else(|| format!("unterminated character class in `{}`", source))?;
+                        ranges.push((lo, hi));
+                    } else {
+                        ranges.push((lo, lo));
+                    }
+                }
+                None => return Err(format!("unterminated character class in `{}`", source)),
+            }
+        }
+        if ranges.is_empty() {
+            return Err(format!("empty character class in `{}`", source));
+        }
+        Ok(Pattern::Class(ranges))
+    }
+
+    fn parse_tag(&mut self) -> Pattern {
+        let mut name = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                name.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        Pattern::Tag(name)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() {
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str) -> String {
+        Pattern::parse(source)
+            .unwrap()
+            .to_matcher_expr(&["int".to_string()])
+            .to_string()
+    }
+
+    #[test]
+    fn parses_single_plus_quantified_tag() {
+        let rendered = render("digit+");
+        assert!(rendered.contains("many1"));
+        assert!(rendered.contains("\"digit\""));
+    }
+
+    #[test]
+    fn parses_sequence_of_terms() {
+        let rendered = render("minus? digit+");
+        assert!(rendered.contains("seq"));
+        assert!(rendered.contains("optional"));
+    }
+
+    #[test]
+    fn parses_alternation() {
+        let rendered = render("int|float");
+        assert!(rendered.contains("alt"));
+    }
+
+    #[test]
+    fn parses_char_literal() {
+        let rendered = render("'-' digit+");
+        assert!(rendered.contains("tag"));
+        assert!(rendered.contains("\'-\'") || rendered.contains("\"-\""));
+    }
+
+    #[test]
+    fn parses_char_class() {
+        let rendered = render("[a-zA-Z_] [a-zA-Z0-9_]*");
+        assert!(rendered.contains("char_class"));
+    }
+
+    #[test]
+    fn rejects_empty_branch() {
+        assert!(Pattern::parse("digit+ | ").is_err());
+    }
+
+    #[test]
+    fn rejects_unterminated_char_literal() {
+        assert!(Pattern::parse("'a").is_err());
+    }
+
+    #[test]
+    fn rejects_unterminated_class() {
+        assert!(Pattern::parse("[a-z").is_err());
+    }
+}
@@ -0,0 +1,419 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{wrap, Token};
+
+/// A single building block of a lexical pattern. Patterns are combined to
+/// describe the language a [Rule] accepts, then compiled into an NFA and
+/// finally a [Dfa] for linear-time scanning.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    /// Matches exactly one occurrence of `ch`.
+    Char(char),
+    /// Matches any character within one of the given inclusive ranges.
+    Class(Vec<(char, char)>),
+    /// Matches each pattern in sequence.
+    Concat(Vec<Pattern>),
+    /// Matches any one of the given patterns.
+    Alt(Vec<Pattern>),
+    /// Matches zero or more repetitions of the inner pattern.
+    Star(Box<Pattern>),
+    /// Matches one or more repetitions of the inner pattern.
+    Plus(Box<Pattern>),
+    /// Matches zero or one repetition of the inner pattern.
+    Opt(Box<Pattern>),
+}
+
+impl Pattern {
+    /// Shorthand for a single inclusive character range.
+    pub fn range(start: char, end: char) -> Pattern {
+        Pattern::Class(vec![(start, end)])
+    }
+
+    /// Wraps this pattern in [Pattern::Star].
+    pub fn star(self) -> Pattern {
+        Pattern::Star(Box::new(self))
+    }
+
+    /// Wraps this pattern in [Pattern::Plus].
+    pub fn plus(self) -> Pattern {
+        Pattern::Plus(Box::new(self))
+    }
+
+    /// Wraps this pattern in [Pattern::Opt].
+    pub fn opt(self) -> Pattern {
+        Pattern::Opt(Box::new(self))
+    }
+
+    fn matches(&self, ch: char) -> bool {
+        match self {
+            Pattern::Char(c) => *c == ch,
+            Pattern::Class(ranges) => ranges.iter().any(|(lo, hi)| *lo <= ch && ch <= *hi),
+            _ => false,
+        }
+    }
+}
+
+/// A pattern plus the tags applied to the token produced when it
+/// matches. When several rules' patterns accept at the same position, the
+/// rule that appears earliest in the slice passed to [Dfa::compile] wins.
+pub struct Rule {
+    pub pattern: Pattern,
+    pub tags: Vec<&'static str>,
+}
+
+/// One state of the Thompson NFA built from a [Rule] set. `accept` holds the
+/// index of the rule this state finishes, if any.
+struct NfaState {
+    epsilons: Vec<usize>,
+    transitions: Vec<(Pattern, usize)>,
+    accept: Option<usize>,
+}
+
+impl NfaState {
+    fn new() -> NfaState {
+        NfaState {
+            epsilons: vec![],
+            transitions: vec![],
+            accept: None,
+        }
+    }
+}
+
+/// A Thompson construction over every rule's pattern, with one shared start
+/// state epsilon-linked to each rule's fragment.
+struct Nfa {
+    states: Vec<NfaState>,
+    start: usize,
+}
+
+impl Nfa {
+    fn push_state(&mut self) -> usize {
+        self.states.push(NfaState::new());
+        self.states.len() - 1
+    }
+
+    /// Builds the fragment for `pattern`, returning its (start, end) states.
+    fn build(&mut self, pattern: &Pattern) -> (usize, usize) {
+        match pattern {
+            Pattern::Char(_) | Pattern::Class(_) => {
+                let start = self.push_state();
+                let end = self.push_state();
+                self.states[start].transitions.push((pattern.clone(), end));
+                (start, end)
+            }
+            Pattern::Concat(patterns) => {
+                let mut patterns = patterns.iter();
+                let first = patterns
+                    .next()
+                    .expect("Concat requires at least one pattern");
+                let (mut start, mut end) = self.build(first);
+                let overall_start = start;
+                for pattern in patterns {
+                    let (next_start, next_end) = self.build(pattern);
+                    self.states[end].epsilons.push(next_start);
+                    end = next_end;
+                    start = next_start;
+                }
+                let _ = start;
+                (overall_start, end)
+            }
+            Pattern::Alt(patterns) => {
+                let start = self.push_state();
+                let end = self.push_state();
+                for pattern in patterns {
+                    let (frag_start, frag_end) = self.build(pattern);
+                    self.states[start].epsilons.push(frag_start);
+                    self.states[frag_end].epsilons.push(end);
+                }
+                (start, end)
+            }
+            Pattern::Star(inner) => {
+                let start = self.push_state();
+                let end = self.push_state();
+                let (frag_start, frag_end) = self.build(inner);
+                self.states[start].epsilons.push(frag_start);
+                self.states[start].epsilons.push(end);
+                self.states[frag_end].epsilons.push(frag_start);
+                self.states[frag_end].epsilons.push(end);
+                (start, end)
+            }
+            Pattern::Plus(inner) => {
+                let (frag_start, frag_end) = self.build(inner);
+                let end = self.push_state();
+                self.states[frag_end].epsilons.push(frag_start);
+                self.states[frag_end].epsilons.push(end);
+                (frag_start, end)
+            }
+            Pattern::Opt(inner) => {
+                let start = self.push_state();
+                let end = self.push_state();
+                let (frag_start, frag_end) = self.build(inner);
+                self.states[start].epsilons.push(frag_start);
+                self.states[start].epsilons.push(end);
+                self.states[frag_end].epsilons.push(end);
+                (start, end)
+            }
+        }
+    }
+
+    /// Builds the NFA for a whole rule set, tagging each rule's accepting
+    /// state with that rule's index (priority order).
+    fn from_rules(rules: &[Rule]) -> Nfa {
+        let mut nfa = Nfa {
+            states: vec![],
+            start: 0,
+        };
+        nfa.start = nfa.push_state();
+        for (rule_index, rule) in rules.iter().enumerate() {
+            let (frag_start, frag_end) = nfa.build(&rule.pattern);
+            nfa.states[nfa.start].epsilons.push(frag_start);
+            nfa.states[frag_end].accept = Some(rule_index);
+        }
+        nfa
+    }
+
+    /// The epsilon-closure of a set of states, as a sorted set.
+    fn closure(&self, states: &[usize]) -> BTreeSet<usize> {
+        let mut closure: BTreeSet<usize> = states.iter().copied().collect();
+        let mut stack: Vec<usize> = states.to_vec();
+        while let Some(state) = stack.pop() {
+            for &next in &self.states[state].epsilons {
+                if closure.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        closure
+    }
+
+    /// The rule index with highest priority (lowest index) accepted by any
+    /// state in `states`, if any.
+    fn accept_of(&self, states: &BTreeSet<usize>) -> Option<usize> {
+        states
+            .iter()
+            .filter_map(|&state| self.states[state].accept)
+            .min()
+    }
+
+    /// The set of states reachable from `states` by consuming `ch`.
+    fn step(&self, states: &BTreeSet<usize>, ch: char) -> BTreeSet<usize> {
+        let mut moved = vec![];
+        for &state in states {
+            for (pattern, target) in &self.states[state].transitions {
+                if pattern.matches(ch) {
+                    moved.push(*target);
+                }
+            }
+        }
+        self.closure(&moved)
+    }
+}
+
+struct DfaState {
+    nfa_states: BTreeSet<usize>,
+    accept: Option<usize>,
+    transitions: HashMap<char, usize>,
+}
+
+/// A deterministic automaton compiled from a [Rule] set via subset
+/// construction. DFA states are built lazily: each one is a set of NFA
+/// states, and a transition for an input character is computed (and cached)
+/// the first time that character is seen from that state, by taking the
+/// epsilon-closure of the move set. This gives maximal-munch scanning over
+/// `str_to_tokens` output in a single linear pass instead of the repeated
+/// re-scans `process_rule` does.
+pub struct Dfa {
+    nfa: Nfa,
+    states: RefCell<Vec<DfaState>>,
+    index: RefCell<HashMap<BTreeSet<usize>, usize>>,
+}
+
+impl Dfa {
+    /// Compiles a rule set into a DFA. Earlier rules take priority when two
+    /// rules' patterns accept at the same position.
+    pub fn compile(rules: &[Rule]) -> Dfa {
+        let nfa = Nfa::from_rules(rules);
+        let dfa = Dfa {
+            nfa,
+            states: RefCell::new(vec![]),
+            index: RefCell::new(HashMap::new()),
+        };
+        let start_set = dfa.nfa.closure(&[dfa.nfa.start]);
+        dfa.state_for(start_set);
+        dfa
+    }
+
+    fn state_for(&self, nfa_states: BTreeSet<usize>) -> usize {
+        if let Some(&existing) = self.index.borrow().get(&nfa_states) {
+            return existing;
+        }
+        let accept = self.nfa.accept_of(&nfa_states);
+        let index = self.states.borrow().len();
+        self.states.borrow_mut().push(DfaState {
+            nfa_states: nfa_states.clone(),
+            accept,
+            transitions: HashMap::new(),
+        });
+        self.index.borrow_mut().insert(nfa_states, index);
+        index
+    }
+
+    /// The DFA's start state.
+    pub fn start(&self) -> usize {
+        0
+    }
+
+    /// The rule index accepted at `state`, if any.
+    pub fn accept(&self, state: usize) -> Option<usize> {
+        self.states.borrow()[state].accept
+    }
+
+    /// The state reached by consuming `ch` from `state`, or `None` if no
+    /// rule's pattern can continue matching.
+    pub fn step(&self, state: usize, ch: char) -> Option<usize> {
+        if let Some(&cached) = self.states.borrow()[state].transitions.get(&ch) {
+            return if cached == usize::MAX {
+                None
+            } else {
+                Some(cached)
+            };
+        }
+        let nfa_states = self.states.borrow()[state].nfa_states.clone();
+        let moved = self.nfa.step(&nfa_states, ch);
+        let next = if moved.is_empty() {
+            None
+        } else {
+            Some(self.state_for(moved))
+        };
+        self.states.borrow_mut()[state]
+            .transitions
+            .insert(ch, next.unwrap_or(usize::MAX));
+        next
+    }
+}
+
+/// Scans pre-tokenized characters (the output of [crate::str_to_tokens])
+/// against a compiled [Dfa], emitting one [Token] per maximal munch: at each
+/// position the automaton is driven forward as far as it can go, and the
+/// token emitted spans up to the last position where some rule accepted
+/// (falling back to passing the single input token through unchanged, with
+/// its existing tags, if none ever did). Input tokens with no character,
+/// such as the trailing empty token, are skipped and not emitted.
+pub fn scan<'a>(dfa: &Dfa, rules: &[Rule], tokens: &[Token<'a>]) -> Vec<Token<'a>> {
+    let mut out = vec![];
+    let mut start = 0;
+    while start < tokens.len() {
+        let Some(first_char) = tokens[start].single_char() else {
+            start += 1;
+            continue;
+        };
+        let mut state = dfa.start();
+        let mut pos = start;
+        let mut last_accept: Option<(usize, usize)> = None;
+        let mut ch = first_char;
+        while let Some(next) = dfa.step(state, ch) {
+            state = next;
+            pos += 1;
+            if let Some(rule_index) = dfa.accept(state) {
+                last_accept = Some((pos, rule_index));
+            }
+            match tokens.get(pos).and_then(Token::single_char) {
+                Some(next_char) => ch = next_char,
+                None => break,
+            }
+        }
+        match last_accept {
+            Some((end, rule_index)) => {
+                out.push(wrap(tokens[start..end].to_vec(), rules[rule_index].tags.clone()));
+                start = end;
+            }
+            None => {
+                out.push(tokens[start].clone());
+                start += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::str_to_tokens;
+
+    fn digit() -> Pattern {
+        Pattern::range('0', '9')
+    }
+
+    fn alpha() -> Pattern {
+        Pattern::Alt(vec![
+            Pattern::range('a', 'z'),
+            Pattern::range('A', 'Z'),
+        ])
+    }
+
+    #[test]
+    fn scans_ints_and_words() {
+        let rules = vec![
+            Rule {
+                pattern: Pattern::Concat(vec![digit().plus()]),
+                tags: vec!["int"],
+            },
+            Rule {
+                pattern: Pattern::Concat(vec![alpha().plus()]),
+                tags: vec!["word"],
+            },
+            Rule {
+                pattern: Pattern::Char(' '),
+                tags: vec!["ws"],
+            },
+        ];
+        let dfa = Dfa::compile(&rules);
+        let text = "ab 12 cd";
+        let mut tokens = str_to_tokens(text);
+        tokens.pop(); // drop the trailing empty_token marker
+        let out = scan(&dfa, &rules, &tokens);
+        let contents: Vec<&str> = out.iter().map(|t| t.content()).collect();
+        assert_eq!(contents, vec!["ab", " ", "12", " ", "cd"]);
+        assert!(out[0].has_tag("word"));
+        assert!(out[2].has_tag("int"));
+    }
+
+    #[test]
+    fn maximal_munch_prefers_longest() {
+        let rules = vec![Rule {
+            pattern: Pattern::Concat(vec![digit().plus()]),
+            tags: vec!["int"],
+        }];
+        let dfa = Dfa::compile(&rules);
+        let text = "123a";
+        let mut tokens = str_to_tokens(text);
+        tokens.pop();
+        let out = scan(&dfa, &rules, &tokens);
+        assert_eq!(out[0].content(), "123");
+        assert_eq!(out[1].content(), "a");
+        assert!(!out[1].has_tag("int"));
+    }
+
+    #[test]
+    fn earlier_rule_wins_priority_ties() {
+        let rules = vec![
+            Rule {
+                pattern: Pattern::Char('a'),
+                tags: vec!["first"],
+            },
+            Rule {
+                pattern: Pattern::Char('a'),
+                tags: vec!["second"],
+            },
+        ];
+        let dfa = Dfa::compile(&rules);
+        let text = "a";
+        let mut tokens = str_to_tokens(text);
+        tokens.pop();
+        let out = scan(&dfa, &rules, &tokens);
+        assert!(out[0].has_tag("first"));
+        assert!(!out[0].has_tag("second"));
+    }
+}
@@ -0,0 +1,13 @@
+use crate::Token;
+
+/// Implemented by types whose token grammar is declared with attributes
+/// instead of hand-written rule closures. The `blex-derive` crate provides
+/// `#[derive(FromTokens)]`, which reads `#[blex(seq = "...", tag = "...")]`
+/// (or `#[blex(one = "...")]`) off each unit variant of an enum and
+/// generates an implementation of this trait from them.
+pub trait FromTokens {
+    /// The combined rule matching every variant's declared pattern, with
+    /// earlier variants taking priority where patterns overlap. Usable
+    /// directly with [crate::process_rule] or [crate::process_rules].
+    fn rule() -> impl Fn(Vec<Token>) -> Option<Vec<Token>>;
+}
@@ -1,13 +1,40 @@
+pub mod automaton;
+pub mod from_tokens;
+pub mod matcher;
+pub mod observer;
+pub mod span;
 pub mod token;
+pub use from_tokens::*;
+pub use observer::*;
+pub use span::*;
 pub use token::*;
 
 /// Processes a rule across a vector of tokens. Starting from the first token,
 /// iteratively applies the rule on a single token. If that application returns
 /// None, continues applying the rule on the token and the next, then the next,
 /// and so on until the function returns Some or there are no tokens left to
-/// process. This process is explained more thoroughly with examples in the 
-/// readme.
+/// process. This process is explained more thoroughly with examples in the
+/// readme. Side-effect-free; see [process_rule_with] to observe each
+/// rewrite as it happens, or [StdoutObserver] to reproduce the trace this
+/// function used to print unconditionally.
 pub fn process_rule(rule: impl Fn(Vec<Token>) -> Option<Vec<Token>>, body: &mut Vec<Token>) {
+    process_rule_with(rule, body, &mut NoopObserver);
+}
+
+/// Processes multiple rules on a vector of tokens. See [process_rule].
+pub fn process_rules(rules: Vec<impl Fn(Vec<Token>) -> Option<Vec<Token>>>, body: &mut Vec<Token>) {
+    process_rules_with(rules, body, &mut NoopObserver);
+}
+
+/// Like [process_rule], but reports each rewrite to `observer` instead of
+/// unconditionally printing it, so the crate stays side-effect-free when
+/// used as a library. Pass [NoopObserver] to silence reporting entirely, or
+/// [StdoutObserver] to reproduce [process_rule]'s trace.
+pub fn process_rule_with(
+    rule: impl Fn(Vec<Token>) -> Option<Vec<Token>>,
+    body: &mut Vec<Token>,
+    observer: &mut impl RewriteObserver,
+) {
     // iterate through each starting position in the body
     let mut start_index: usize = 0;
     'current_start: while start_index < body.len() {
@@ -19,7 +46,7 @@ pub fn process_rule(rule: impl Fn(Vec<Token>) -> Option<Vec<Token>>, body: &mut
         let mut applied: Option<Vec<Token>> = rule(tokens);
 
         // if the rule returns Some or requests tokens past the end, finish the iteration
-        while let None = applied {
+        while applied.is_none() {
             end_index += 1;
             if end_index > body.len() {
                 start_index += 1;
@@ -32,10 +59,7 @@ pub fn process_rule(rule: impl Fn(Vec<Token>) -> Option<Vec<Token>>, body: &mut
         // we know that the returned tokens will exist at this point, so unwrap() is safe
         let replacement: Vec<Token> = applied.unwrap();
 
-        println!("\nReplacing");
-        print_tokens(body[start_index..end_index].to_vec());
-        println!("with");
-        print_tokens(replacement.clone());
+        observer.on_rewrite(&body[start_index..end_index], &replacement, start_index);
 
         let r_len = replacement.len();
 
@@ -47,10 +71,15 @@ pub fn process_rule(rule: impl Fn(Vec<Token>) -> Option<Vec<Token>>, body: &mut
     }
 }
 
-/// Processes multiple rules on a vector of tokens. See [process_rule].
-pub fn process_rules(rules: Vec<impl Fn(Vec<Token>) -> Option<Vec<Token>>>, body: &mut Vec<Token>) {
+/// Like [process_rules], but reports each rewrite to `observer`. See
+/// [process_rule_with].
+pub fn process_rules_with(
+    rules: Vec<impl Fn(Vec<Token>) -> Option<Vec<Token>>>,
+    body: &mut Vec<Token>,
+    observer: &mut impl RewriteObserver,
+) {
     for rule in rules {
-        process_rule(rule, body);
+        process_rule_with(rule, body, observer);
     }
 }
 
@@ -153,7 +182,7 @@ mod tests {
     fn digit_rule(mut tokens: Vec<Token>) -> Option<Vec<Token>> {
         if let TokenStructure::Single(tok) = tokens_structure(&tokens) {
             let ch = tok.single_char().unwrap_or_default();
-            if ch.is_digit(10) {
+            if ch.is_ascii_digit() {
                 tokens[0].tags.push("digit");
                 if ch != '0' {
                     tokens[0].tags.push("nonzero");
@@ -264,7 +293,8 @@ mod tests {
         Now watch out for my spin attack...";
         let mut body = str_to_tokens(text);
         for _ in 0..1000 {
-            black_box(process_rules(word_rules(), &mut body));
+            process_rules(word_rules(), &mut body);
+            black_box(&body);
         }
     }
 
@@ -305,4 +335,60 @@ mod tests {
         println!("{}", token_from_string("Hi", vec!["test"]).has_tag("test"));
         print_tokens(str_to_tokens("a b blex ab abab"));
     }
+
+    #[test]
+    fn str_to_tokens_handles_accented_latin() {
+        let text = "caf\u{e9}";
+        let tokens = str_to_tokens(text);
+        // 4 chars ('c', 'a', 'f', 'é') plus the trailing empty token marker.
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[3].content(), "\u{e9}");
+        assert_eq!(tokens[3].indices, 3..5);
+    }
+
+    #[test]
+    fn str_to_tokens_handles_cjk() {
+        let text = "\u{4f60}\u{597d}";
+        let tokens = str_to_tokens(text);
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].content(), "\u{4f60}");
+        assert_eq!(tokens[1].content(), "\u{597d}");
+    }
+
+    #[test]
+    fn str_to_tokens_handles_emoji() {
+        let text = "a\u{1f600}b";
+        let tokens = str_to_tokens(text);
+        // the emoji is a 4-byte scalar value but still exactly one token.
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[1].content(), "\u{1f600}");
+        assert_eq!(tokens[1].indices, 1..5);
+    }
+
+    struct CountingObserver {
+        rewrites: usize,
+    }
+
+    impl RewriteObserver for CountingObserver {
+        fn on_rewrite(&mut self, _from: &[Token], _to: &[Token], _at: usize) {
+            self.rewrites += 1;
+        }
+    }
+
+    #[test]
+    fn process_rule_with_reports_rewrites_to_observer() {
+        let mut body = str_to_tokens("A space");
+        let mut observer = CountingObserver { rewrites: 0 };
+        process_rule_with(whitespace_rule, &mut body, &mut observer);
+        assert!(observer.rewrites > 0);
+    }
+
+    #[test]
+    fn process_rule_is_silent_by_default() {
+        // NoopObserver means this shouldn't print anything; mainly a smoke
+        // test that the default path still rewrites correctly.
+        let mut body = str_to_tokens("123 040 k");
+        process_rules(int_rules(), &mut body);
+        assert!(body[0].has_tag("int"));
+    }
 }
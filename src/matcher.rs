@@ -0,0 +1,310 @@
+use crate::{wrap, Token};
+
+/// The outcome of trying to match a [Matcher] against the front of a token
+/// slice. Mirrors the three-way protocol `process_rule` already uses for
+/// hand-written rule closures: not enough tokens yet, a definite non-match,
+/// or a definite match consuming some prefix.
+pub enum MatchResult<'a> {
+    /// The matcher consumed `count` of the leading tokens and produced
+    /// `produced` in their place.
+    Consumed {
+        count: usize,
+        produced: Vec<Token<'a>>,
+    },
+    /// There isn't enough input yet to know whether this matches; ask for
+    /// more tokens (maps to `process_rule`'s `None`).
+    NeedMore,
+    /// This matcher definitely does not match the given prefix.
+    NoMatch,
+}
+
+/// A composable unit of matching over a token prefix, in the spirit of a
+/// parser combinator. Implementors only need to describe how to match the
+/// very front of a token slice; [seq], [alt], [Matcher::many1] and friends
+/// build larger matchers out of smaller ones.
+pub trait Matcher {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a>;
+
+    /// Matches one or more repetitions of this matcher, greedily.
+    fn many1(self) -> Many1<Self>
+    where
+        Self: Sized,
+    {
+        Many1(self)
+    }
+
+    /// Matches zero or one repetition of this matcher.
+    fn optional(self) -> Optional<Self>
+    where
+        Self: Sized,
+    {
+        Optional(self)
+    }
+
+    /// Folds everything this matcher consumes into a single token carrying
+    /// `tags`.
+    fn wrap_as(self, tags: Vec<&'static str>) -> WrapAs<Self>
+    where
+        Self: Sized,
+    {
+        WrapAs { inner: self, tags }
+    }
+}
+
+/// Matches a single token carrying `tag`.
+pub struct Tag(pub &'static str);
+
+/// Matches one token carrying a given tag.
+pub fn tag(tag: &'static str) -> Tag {
+    Tag(tag)
+}
+
+impl Matcher for Tag {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        match toks.first() {
+            None => MatchResult::NeedMore,
+            Some(tok) if tok.has_tag(self.0) => MatchResult::Consumed {
+                count: 1,
+                produced: vec![tok.clone()],
+            },
+            Some(_) => MatchResult::NoMatch,
+        }
+    }
+}
+
+/// Matches a single token whose first character falls within one of the
+/// given inclusive ranges.
+pub struct CharClass(pub Vec<(char, char)>);
+
+/// Matches a single token whose first character falls within one of the
+/// given inclusive ranges.
+pub fn char_class(ranges: Vec<(char, char)>) -> CharClass {
+    CharClass(ranges)
+}
+
+impl Matcher for CharClass {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        match toks.first() {
+            None => MatchResult::NeedMore,
+            Some(tok) => match tok.single_char() {
+                Some(ch) if self.0.iter().any(|(lo, hi)| *lo <= ch && ch <= *hi) => {
+                    MatchResult::Consumed {
+                        count: 1,
+                        produced: vec![tok.clone()],
+                    }
+                }
+                _ => MatchResult::NoMatch,
+            },
+        }
+    }
+}
+
+/// Matches `a` immediately followed by `b`.
+pub struct Seq<A, B>(pub A, pub B);
+
+/// Matches `a` immediately followed by `b`.
+pub fn seq<A: Matcher, B: Matcher>(a: A, b: B) -> Seq<A, B> {
+    Seq(a, b)
+}
+
+impl<A: Matcher, B: Matcher> Matcher for Seq<A, B> {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        match self.0.match_prefix(toks) {
+            MatchResult::Consumed {
+                count,
+                mut produced,
+            } => match self.1.match_prefix(&toks[count..]) {
+                MatchResult::Consumed {
+                    count: count_b,
+                    produced: produced_b,
+                } => {
+                    produced.extend(produced_b);
+                    MatchResult::Consumed {
+                        count: count + count_b,
+                        produced,
+                    }
+                }
+                other => other,
+            },
+            other => other,
+        }
+    }
+}
+
+/// Matches whichever of `a` or `b` accepts, preferring `a`.
+pub struct Alt<A, B>(pub A, pub B);
+
+/// Matches whichever of `a` or `b` accepts, preferring `a`.
+pub fn alt<A: Matcher, B: Matcher>(a: A, b: B) -> Alt<A, B> {
+    Alt(a, b)
+}
+
+impl<A: Matcher, B: Matcher> Matcher for Alt<A, B> {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        match self.0.match_prefix(toks) {
+            MatchResult::NoMatch => self.1.match_prefix(toks),
+            other => other,
+        }
+    }
+}
+
+/// Matches the negation of `inner`: succeeds, consuming nothing, exactly
+/// where `inner` would not match.
+pub struct Not<M>(pub M);
+
+/// Matches the negation of `inner`: succeeds, consuming nothing, exactly
+/// where `inner` would not match.
+pub fn not<M: Matcher>(inner: M) -> Not<M> {
+    Not(inner)
+}
+
+impl<M: Matcher> Matcher for Not<M> {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        match self.0.match_prefix(toks) {
+            MatchResult::Consumed { .. } => MatchResult::NoMatch,
+            MatchResult::NoMatch => MatchResult::Consumed {
+                count: 0,
+                produced: vec![],
+            },
+            MatchResult::NeedMore => MatchResult::NeedMore,
+        }
+    }
+}
+
+/// Matches one or more repetitions of `M`, greedily. See [Matcher::many1].
+pub struct Many1<M>(M);
+
+impl<M: Matcher> Matcher for Many1<M> {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        let mut count = 0;
+        let mut produced = vec![];
+        loop {
+            match self.0.match_prefix(&toks[count..]) {
+                MatchResult::Consumed {
+                    count: step_count,
+                    produced: step_produced,
+                } => {
+                    count += step_count;
+                    produced.extend(step_produced);
+                }
+                MatchResult::NeedMore => return MatchResult::NeedMore,
+                MatchResult::NoMatch => {
+                    return if count == 0 {
+                        MatchResult::NoMatch
+                    } else {
+                        MatchResult::Consumed { count, produced }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Matches zero or one repetition of `M`. See [Matcher::optional].
+pub struct Optional<M>(M);
+
+impl<M: Matcher> Matcher for Optional<M> {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        match self.0.match_prefix(toks) {
+            MatchResult::NoMatch => MatchResult::Consumed {
+                count: 0,
+                produced: vec![],
+            },
+            other => other,
+        }
+    }
+}
+
+/// Folds everything `inner` consumes into one wrapped token. See
+/// [Matcher::wrap_as].
+pub struct WrapAs<M> {
+    inner: M,
+    tags: Vec<&'static str>,
+}
+
+impl<M: Matcher> Matcher for WrapAs<M> {
+    fn match_prefix<'a>(&self, toks: &[Token<'a>]) -> MatchResult<'a> {
+        match self.inner.match_prefix(toks) {
+            MatchResult::Consumed { count, produced } => MatchResult::Consumed {
+                count,
+                produced: vec![wrap(produced, self.tags.clone())],
+            },
+            other => other,
+        }
+    }
+}
+
+/// Adapts a [Matcher] into the `Fn(Vec<Token>) -> Option<Vec<Token>>` shape
+/// `process_rule`/`process_rules` expect, so combinator-built matchers drop
+/// straight in next to hand-written rules. `NeedMore` becomes `None`,
+/// `NoMatch` returns the tokens unchanged, and `Consumed` returns the
+/// produced tokens followed by the untouched remainder.
+pub fn into_rule<M: Matcher>(matcher: M) -> impl Fn(Vec<Token>) -> Option<Vec<Token>> {
+    move |tokens: Vec<Token>| match matcher.match_prefix(&tokens) {
+        MatchResult::NeedMore => None,
+        MatchResult::NoMatch => Some(tokens),
+        MatchResult::Consumed { count, mut produced } => {
+            produced.extend_from_slice(&tokens[count..]);
+            Some(produced)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{process_rule, str_to_tokens};
+
+    fn digit_tagged<'a>(mut tokens: Vec<Token<'a>>) -> Vec<Token<'a>> {
+        for tok in &mut tokens {
+            if tok.single_char().unwrap_or_default().is_ascii_digit() {
+                tok.tags.push("digit");
+            }
+        }
+        tokens
+    }
+
+    #[test]
+    fn tag_matches_single_token() {
+        let tokens = digit_tagged(str_to_tokens("5"));
+        match tag("digit").match_prefix(&tokens[0..1]) {
+            MatchResult::Consumed { count, produced } => {
+                assert_eq!(count, 1);
+                assert_eq!(produced.len(), 1);
+            }
+            _ => panic!("expected a match"),
+        }
+    }
+
+    #[test]
+    fn char_class_matches_a_range() {
+        let tokens = str_to_tokens("a1");
+        match char_class(vec![('a', 'z')]).match_prefix(&tokens[0..1]) {
+            MatchResult::Consumed { count, .. } => assert_eq!(count, 1),
+            _ => panic!("expected a match"),
+        }
+        match char_class(vec![('a', 'z')]).match_prefix(&tokens[1..2]) {
+            MatchResult::NoMatch => {}
+            _ => panic!("expected no match"),
+        }
+    }
+
+    #[test]
+    fn many1_consumes_greedily() {
+        let tokens = digit_tagged(str_to_tokens("123a"));
+        match tag("digit").many1().match_prefix(&tokens) {
+            MatchResult::Consumed { count, .. } => assert_eq!(count, 3),
+            _ => panic!("expected a match"),
+        }
+    }
+
+    #[test]
+    fn seq_and_not_compose_into_a_rule() {
+        let int_matcher = seq(tag("digit").many1(), not(tag("digit"))).wrap_as(vec!["int"]);
+        let rule = into_rule(int_matcher);
+        let mut body = digit_tagged(str_to_tokens("12 3"));
+        process_rule(rule, &mut body);
+        assert!(body[0].has_tag("int"));
+        assert_eq!(body[0].content(), "12");
+    }
+}
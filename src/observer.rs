@@ -0,0 +1,30 @@
+use crate::{print_tokens, Token};
+
+/// Notified whenever [crate::process_rule_with] rewrites a span of tokens,
+/// so callers can redirect, silence, or otherwise act on that step instead
+/// of it being hardcoded to stdout.
+pub trait RewriteObserver {
+    /// Called just before `body[at..at + from.len()]` is replaced with `to`.
+    fn on_rewrite(&mut self, from: &[Token], to: &[Token], at: usize);
+}
+
+/// An observer that does nothing. The default for [crate::process_rule] and
+/// [crate::process_rules].
+pub struct NoopObserver;
+
+impl RewriteObserver for NoopObserver {
+    fn on_rewrite(&mut self, _from: &[Token], _to: &[Token], _at: usize) {}
+}
+
+/// An observer reproducing the println!-based trace `process_rule` used to
+/// print unconditionally.
+pub struct StdoutObserver;
+
+impl RewriteObserver for StdoutObserver {
+    fn on_rewrite(&mut self, from: &[Token], to: &[Token], _at: usize) {
+        println!("\nReplacing");
+        print_tokens(from.to_vec());
+        println!("with");
+        print_tokens(to.to_vec());
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+use crate::Token;
+
+/// A precomputed index of line-start byte offsets into a source string,
+/// letting byte offsets from [crate::Token::indices] be turned into
+/// human-readable `(line, column)` positions.
+pub struct LineMap<'a> {
+    body: &'a str,
+    /// Byte offset where each line begins: index 0 is always `0`, and every
+    /// other entry is the byte just past a `\n`.
+    starts: Vec<usize>,
+}
+
+impl<'a> LineMap<'a> {
+    /// Builds a line map over `body`.
+    pub fn new(body: &'a str) -> LineMap<'a> {
+        let mut starts = vec![0];
+        for (index, ch) in body.char_indices() {
+            if ch == '\n' {
+                starts.push(index + ch.len_utf8());
+            }
+        }
+        LineMap { body, starts }
+    }
+
+    /// Locates the 1-based `(line, column)` of `byte`, with column counted
+    /// in chars (not bytes) from the start of its line.
+    pub fn locate(&self, byte: usize) -> (usize, usize) {
+        let line_index = match self.starts.binary_search(&byte) {
+            Ok(exact) => exact,
+            Err(insert_at) => insert_at.saturating_sub(1),
+        };
+        let line_start = self.starts[line_index];
+        let column = self.body[line_start..byte].chars().count() + 1;
+        (line_index + 1, column)
+    }
+
+    /// The source text of the line containing `byte`, without its trailing
+    /// newline.
+    pub fn line_text(&self, byte: usize) -> &'a str {
+        let (line, _) = self.locate(byte);
+        let start = self.starts[line - 1];
+        let end = self.starts.get(line).copied().unwrap_or(self.body.len());
+        self.body[start..end].trim_end_matches('\n')
+    }
+}
+
+impl<'a> Token<'a> {
+    /// This token's start and end positions as `(line, column)` pairs,
+    /// resolved via a precomputed [LineMap].
+    pub fn span(&self, map: &LineMap) -> ((usize, usize), (usize, usize)) {
+        (
+            map.locate(self.indices.start),
+            map.locate(self.indices.end),
+        )
+    }
+}
+
+/// An error tied to a specific token's position in the source, for
+/// surfacing precise lexer diagnostics instead of a silent `None`.
+#[derive(Debug)]
+pub struct LexError {
+    pub message: String,
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    source_line: String,
+}
+
+impl LexError {
+    /// Builds a `LexError` located at `token`'s span.
+    pub fn new(message: impl Into<String>, token: &Token, map: &LineMap) -> LexError {
+        let (start, end) = token.span(map);
+        LexError {
+            message: message.into(),
+            start,
+            end,
+            source_line: map.line_text(token.indices.start).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:{}: {}", self.start.0, self.start.1, self.message)?;
+        write!(f, "{}", self.source_line)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::str_to_tokens;
+
+    #[test]
+    fn locates_first_and_later_lines() {
+        let text = "abc\ndef\nghi";
+        let map = LineMap::new(text);
+        assert_eq!(map.locate(0), (1, 1));
+        assert_eq!(map.locate(2), (1, 3));
+        assert_eq!(map.locate(4), (2, 1));
+        assert_eq!(map.locate(9), (3, 2));
+    }
+
+    #[test]
+    fn line_text_excludes_newline() {
+        let text = "abc\ndef\nghi";
+        let map = LineMap::new(text);
+        assert_eq!(map.line_text(5), "def");
+        assert_eq!(map.line_text(9), "ghi");
+    }
+
+    #[test]
+    fn token_span_matches_line_map() {
+        let text = "foo\nbar";
+        let tokens = str_to_tokens(text);
+        let map = LineMap::new(text);
+        // tokens[5] is 'a' in "bar", at byte index 5.
+        let (start, end) = tokens[5].span(&map);
+        assert_eq!(start, (2, 2));
+        assert_eq!(end, (2, 3));
+    }
+
+    #[test]
+    fn lex_error_displays_line_col_and_source() {
+        let text = "foo\nbad!\nbaz";
+        let tokens = str_to_tokens(text);
+        let map = LineMap::new(text);
+        let err = LexError::new("unexpected character", &tokens[7], &map);
+        let rendered = err.to_string();
+        assert!(rendered.starts_with("2:4: unexpected character"));
+        assert!(rendered.contains("bad!"));
+    }
+}
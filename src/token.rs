@@ -76,16 +76,41 @@ pub fn print_tokens(tokens: Vec<Token>) {
     }
 }
 
-/// Chops up a string slice into a vector of owned tokens. Also appends an empty
-/// token to the tail of the vector to enable certain lexing functions like
-/// scanning for words.
-pub fn str_to_tokens<'a>(body: &'a str) -> Vec<Token> {
+/// Chops up a string slice into a vector of owned tokens, one per Unicode
+/// scalar value (`char`). Iterates `char_indices` rather than byte offsets so
+/// multi-byte characters (accents, CJK, emoji, ...) are never sliced across a
+/// non-boundary. Also appends an empty token to the tail of the vector to
+/// enable certain lexing functions like scanning for words.
+pub fn str_to_tokens<'a>(body: &'a str) -> Vec<Token<'a>> {
     let mut tokens: Vec<Token> = vec![];
-    for index in 0..body.len() {
+    for (index, ch) in body.char_indices() {
+        let end = index + ch.len_utf8();
         tokens.push(Token {
             body,
-            indices: index..index + 1,
-            tags: vec![&body[index..index + 1]],
+            indices: index..end,
+            tags: vec![&body[index..end]],
+        });
+    }
+    tokens.push(empty_token());
+    tokens
+}
+
+/// Like [str_to_tokens], but segments on extended grapheme clusters instead
+/// of `char`s, so a base character plus its combining marks, or a flag
+/// emoji, becomes a single token rather than one token per codepoint.
+/// Requires the `graphemes` feature.
+#[cfg(feature = "graphemes")]
+pub fn str_to_grapheme_tokens<'a>(body: &'a str) -> Vec<Token<'a>> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let mut tokens: Vec<Token> = vec![];
+    for grapheme in body.grapheme_indices(true) {
+        let (index, slice) = grapheme;
+        let end = index + slice.len();
+        tokens.push(Token {
+            body,
+            indices: index..end,
+            tags: vec![slice],
         });
     }
     tokens.push(empty_token());
@@ -116,7 +141,7 @@ pub enum TokenStructure<'a> {
 
 /// Goes with the TokenStructure enum. Specifies whether a borrowed vector of
 /// tokens consists of multiple tokens, a single token, or none at all.
-pub fn tokens_structure<'a>(tokens: &'a Vec<Token<'a>>) -> TokenStructure<'a> {
+pub fn tokens_structure<'a>(tokens: &'a [Token<'a>]) -> TokenStructure<'a> {
     if tokens.len() == 1 {
         TokenStructure::Single(&tokens[0])
     } else if tokens.len() > 1 {
@@ -0,0 +1,54 @@
+use blex::{process_rule, str_to_tokens, FromTokens, Token};
+
+#[derive(blex_derive::FromTokens)]
+#[allow(dead_code)]
+enum Grammar {
+    #[blex(seq = "digit+", tag = "int")]
+    Int,
+    #[blex(one = "ws", tag = "ws")]
+    Whitespace,
+}
+
+fn tag_chars(mut tokens: Vec<Token>) -> Option<Vec<Token>> {
+    for tok in &mut tokens {
+        let ch = tok.single_char().unwrap_or_default();
+        if ch.is_ascii_digit() {
+            tok.tags.push("digit");
+        } else if ch.is_whitespace() {
+            tok.tags.push("ws");
+        }
+    }
+    Some(tokens)
+}
+
+#[test]
+fn derived_rule_wraps_digit_runs() {
+    let mut body = str_to_tokens("12 3");
+    process_rule(tag_chars, &mut body);
+    process_rule(Grammar::rule(), &mut body);
+
+    assert!(body[0].has_tag("int"));
+    assert_eq!(body[0].content(), "12");
+    assert!(body[1].has_tag("ws"));
+    assert!(body[2].has_tag("int"));
+    assert_eq!(body[2].content(), "3");
+}
+
+#[test]
+fn earlier_variant_wins_on_overlap() {
+    #[derive(blex_derive::FromTokens)]
+    #[allow(dead_code)]
+    enum Overlapping {
+        #[blex(one = "digit", tag = "first")]
+        First,
+        #[blex(one = "digit", tag = "second")]
+        Second,
+    }
+
+    let mut body = str_to_tokens("5");
+    process_rule(tag_chars, &mut body);
+    process_rule(Overlapping::rule(), &mut body);
+
+    assert!(body[0].has_tag("first"));
+    assert!(!body[0].has_tag("second"));
+}